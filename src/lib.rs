@@ -0,0 +1,27 @@
+extern crate rustc_serialize as serialize;
+#[cfg(feature = "serde_type")]
+extern crate serde;
+extern crate serde_json;
+extern crate iron;
+extern crate plugin;
+extern crate handlebars;
+extern crate hprof;
+extern crate notify;
+#[cfg(feature = "rust-embed")]
+extern crate rust_embed;
+#[macro_use]
+extern crate log;
+
+pub use middleware::{HandlebarsEngine, Template};
+pub use source::{Source, SourceError};
+pub use sources::directory::DirectorySource;
+#[cfg(feature = "rust-embed")]
+pub use sources::embedded::EmbeddedSource;
+#[cfg(feature = "script_helper")]
+pub use sources::script::ScriptSource;
+pub use watch::Watchable;
+
+mod middleware;
+mod source;
+mod sources;
+mod watch;
@@ -1,5 +1,4 @@
 use std::sync::RwLock;
-use std::error::Error;
 
 use iron::prelude::*;
 use iron::status;
@@ -7,32 +6,68 @@ use iron::{AfterMiddleware, typemap};
 use iron::modifier::Modifier;
 use plugin::Plugin as PluginFor;
 use iron::headers::ContentType;
+use iron::mime::Mime;
 
 use handlebars::Handlebars;
+use serde_json::Value as Json;
 #[cfg(not(feature = "serde_type"))]
-use serialize::json::{ToJson, Json};
+use serialize::json::ToJson;
 #[cfg(feature = "serde_type")]
 use serde::ser::Serialize as ToJson;
-#[cfg(feature = "serde_type")]
-use serde_json::value::{self, Value as Json};
 
-use source::{Source, SourceError};
-use sources::directory::DirectorySource;
+use crate::source::{Source, SourceError};
+use crate::sources::directory::DirectorySource;
+
+/// Key injected into the page's data when rendering it through a layout, so
+/// the layout template can emit the page with e.g. `{{{body}}}`.
+const LAYOUT_BODY_KEY: &str = "body";
+
+fn with_body(value: &Json, body: String) -> Json {
+    let mut map = match *value {
+        Json::Object(ref map) => map.clone(),
+        _ => serde_json::Map::new(),
+    };
+    map.insert(LAYOUT_BODY_KEY.to_string(), Json::String(body));
+    Json::Object(map)
+}
+
+/// Resolves the layout to render a response through: a `Template`'s own
+/// override if it has one, otherwise the engine's default.
+fn effective_layout(template_layout: &Option<Option<String>>, engine_layout: &Option<String>) -> Option<String> {
+    match *template_layout {
+        Some(ref overridden) => overridden.clone(),
+        None => engine_layout.clone(),
+    }
+}
 
-use hprof;
+/// Resolves the `Content-Type` header to set on a response: a `Template`'s
+/// own override if it has one, otherwise `text/html`.
+fn effective_content_type(content_type: Option<Mime>) -> ContentType {
+    content_type.map(ContentType).unwrap_or_else(ContentType::html)
+}
 
 #[derive(Clone)]
 pub struct Template {
     name: String,
     value: Json,
+    content_type: Option<Mime>,
+    layout: Option<Option<String>>,
 }
 
 #[cfg(not(feature = "serde_type"))]
 impl Template {
     pub fn new<T: ToJson>(name: &str, value: T) -> Template {
+        // rustc-serialize's `Json` has no direct bridge into `serde_json`,
+        // so we round-trip it through its own JSON text form; every other
+        // representation in this crate is `serde_json::Value`, which is
+        // what the handlebars version we depend on renders with.
+        let text = value.to_json().to_string();
+        let value = serde_json::from_str(&text).expect("rustc-serialize always emits valid JSON");
         Template {
             name: name.to_string(),
-            value: value.to_json(),
+            value,
+            content_type: None,
+            layout: None,
         }
     }
 }
@@ -40,16 +75,44 @@ impl Template {
 #[cfg(feature = "serde_type")]
 impl Template {
     pub fn new<T: ToJson>(name: &str, value: T) -> Template {
+        let value = serde_json::to_value(&value).expect("value must serialize to JSON");
         Template {
             name: name.to_string(),
-            value: value::to_value(&value),
+            value,
+            content_type: None,
+            layout: None,
         }
     }
 }
 
+impl Template {
+    /// Overrides the `Content-Type` the middleware sets on the response,
+    /// for templates that don't render HTML (JSON, XML, SVG, CSV, ...).
+    pub fn with_content_type(mut self, content_type: Mime) -> Template {
+        self.content_type = Some(content_type);
+        self
+    }
+
+    /// Wraps this response in `layout` instead of `HandlebarsEngine`'s
+    /// default layout.
+    pub fn with_layout(mut self, layout: &str) -> Template {
+        self.layout = Some(Some(layout.to_string()));
+        self
+    }
+
+    /// Renders this response on its own, even if `HandlebarsEngine` has a
+    /// default layout configured.
+    pub fn without_layout(mut self) -> Template {
+        self.layout = Some(None);
+        self
+    }
+}
+
 pub struct HandlebarsEngine {
-    pub sources: Vec<Box<Source + Send + Sync>>,
-    pub registry: RwLock<Box<Handlebars>>,
+    pub sources: Vec<Box<dyn Source + Send + Sync>>,
+    pub registry: RwLock<Box<Handlebars<'static>>>,
+    dev_mode: bool,
+    layout: Option<String>,
 }
 
 impl typemap::Key for HandlebarsEngine {
@@ -81,17 +144,17 @@ impl HandlebarsEngine {
         hbs.add(Box::new(DirectorySource::new(prefix, suffix)));
         match hbs.reload() {
             Ok(_) => hbs,
-            Err(e) => panic!("Failed to init from directory: {}", e.description()),
+            Err(e) => panic!("Failed to init from directory: {}", e),
         }
     }
 
     /// #[Deprecated], for backward compaitibility only
-    pub fn from(prefix: &str, suffix: &str, custom: Handlebars) -> HandlebarsEngine {
+    pub fn from(prefix: &str, suffix: &str, custom: Handlebars<'static>) -> HandlebarsEngine {
         let mut hbs = HandlebarsEngine::from2(custom);
         hbs.add(Box::new(DirectorySource::new(prefix, suffix)));
         match hbs.reload() {
             Ok(_) => hbs,
-            Err(e) => panic!("Failed to init from directory: {}", e.description()),
+            Err(e) => panic!("Failed to init from directory: {}", e),
         }
     }
 
@@ -99,25 +162,77 @@ impl HandlebarsEngine {
         HandlebarsEngine {
             sources: Vec::new(),
             registry: RwLock::new(Box::new(Handlebars::new())),
+            dev_mode: false,
+            layout: None,
         }
     }
 
-    pub fn from2(reg: Handlebars) -> HandlebarsEngine {
+    pub fn from2(reg: Handlebars<'static>) -> HandlebarsEngine {
         HandlebarsEngine {
             sources: Vec::new(),
             registry: RwLock::new(Box::new(reg)),
+            dev_mode: false,
+            layout: None,
         }
     }
 
-    pub fn add(&mut self, source: Box<Source + Send + Sync>) {
+    pub fn add(&mut self, source: Box<dyn Source + Send + Sync>) {
         self.sources.push(source);
     }
 
+    /// Enables or disables dev mode.
+    ///
+    /// With dev mode on, `AfterMiddleware::after` checks every source for
+    /// changes before each render and re-registers only what changed, so
+    /// editing a template on disk shows up without restarting the server.
+    /// Leave it off in production: the extra per-request change check is
+    /// wasted work once templates stop moving.
+    pub fn set_dev_mode(&mut self, dev_mode: bool) {
+        self.dev_mode = dev_mode;
+    }
+
+    pub fn is_dev_mode(&self) -> bool {
+        self.dev_mode
+    }
+
+    /// Sets (or clears) the layout every page template is wrapped in.
+    ///
+    /// When set, `AfterMiddleware::after` renders the page template first,
+    /// then renders `layout` with the page's data plus a `"body"` key
+    /// holding the rendered page, so the layout can emit it with
+    /// `{{{body}}}`. A `Template` can override this per response via
+    /// `Template::with_layout`/`without_layout`.
+    pub fn set_layout(&mut self, layout: Option<String>) {
+        self.layout = layout;
+    }
+
     pub fn reload(&self) -> Result<(), SourceError> {
         let mut hbs = self.registry.write().unwrap();
         hbs.clear_templates();
         for s in self.sources.iter() {
-            try!(s.load(&mut hbs))
+            s.load(&mut hbs)?
+        }
+        Ok(())
+    }
+
+    /// Re-registers only the sources that report a change, taking the
+    /// registry's write lock only if at least one of them actually does,
+    /// and only calling back into the sources that changed.
+    pub fn reload_if_changed(&self) -> Result<(), SourceError> {
+        let mut changed = Vec::new();
+        for s in self.sources.iter() {
+            if s.has_changed()? {
+                changed.push(s);
+            }
+        }
+
+        if changed.is_empty() {
+            return Ok(());
+        }
+
+        let mut hbs = self.registry.write().unwrap();
+        for s in changed {
+            s.reload_if_changed(&mut hbs)?;
         }
         Ok(())
     }
@@ -126,29 +241,42 @@ impl HandlebarsEngine {
 impl AfterMiddleware for HandlebarsEngine {
     fn after(&self, _: &mut Request, r: Response) -> IronResult<Response> {
 
-        let guard = hprof::enter("handlebars");
+        let _guard = hprof::enter("handlebars");
+
+        if self.dev_mode {
+            if let Err(e) = self.reload_if_changed() {
+                info!("{}", e);
+                return Err(IronError::new(e, status::InternalServerError));
+            }
+        }
 
         let mut resp = r;
-        let page_wrapper = resp.extensions
-                               .get::<HandlebarsEngine>()
-                               .as_ref()
-                               .and_then(|h| {
-                                   let hbs = self.registry.read().unwrap();
-                                   Some(hbs.render(&h.name, &h.value))
-                               });
+        let template = resp.extensions.get::<HandlebarsEngine>().cloned();
+        let page_wrapper = template.as_ref().map(|h| {
+            let hbs = self.registry.read().unwrap();
+            let layout = effective_layout(&h.layout, &self.layout);
+
+            hbs.render(&h.name, &h.value).and_then(|page| {
+                match layout {
+                    Some(ref layout_name) => hbs.render(layout_name, &with_body(&h.value, page)),
+                    None => Ok(page),
+                }
+            })
+        });
 
         match page_wrapper {
             Some(page_result) => {
                 match page_result {
                     Ok(page) => {
                         if !resp.headers.has::<ContentType>() {
-                            resp.headers.set(ContentType::html());
+                            let content_type = effective_content_type(template.and_then(|h| h.content_type));
+                            resp.headers.set(content_type);
                         }
                         resp.set_mut(page);
                         Ok(resp)
                     }
                     Err(e) => {
-                        info!("{}", e.description());
+                        info!("{}", e);
                         Err(IronError::new(e, status::InternalServerError))
                     }
                 }
@@ -162,8 +290,10 @@ impl AfterMiddleware for HandlebarsEngine {
 mod test {
     use std::collections::BTreeMap;
     use iron::prelude::*;
-    use middleware::*;
-    use handlebars::{Handlebars, RenderError, RenderContext, Helper, Context};
+    use iron::mime::Mime;
+    use iron::headers::ContentType;
+    use crate::middleware::*;
+    use handlebars::{Handlebars, RenderError, RenderContext, Helper, Context, Output};
 
     fn hello_world() -> IronResult<Response> {
         let resp = Response::new();
@@ -176,7 +306,7 @@ mod test {
 
     #[test]
     fn test_resp_set() {
-        let mut resp = hello_world().ok().expect("response expected");
+        let mut resp = hello_world().expect("response expected");
 
         // use response plugin to retrieve a cloned template for testing
         match resp.get::<HandlebarsEngine>() {
@@ -187,7 +317,7 @@ mod test {
                             .unwrap()
                             .get(&"title".to_string())
                             .unwrap()
-                            .as_string()
+                            .as_str()
                             .unwrap(),
                            "Handlebars on Iron");
             }
@@ -200,12 +330,51 @@ mod test {
         let hbs = HandlebarsEngine::new2();
         let mut reg = hbs.registry.write().unwrap();
         reg.register_helper("ignore",
-                            Box::new(|_: &Context,
-                                      _: &Helper,
+                            Box::new(|_: &Helper,
                                       _: &Handlebars,
-                                      _: &mut RenderContext|
+                                      _: &Context,
+                                      _: &mut RenderContext,
+                                      _: &mut dyn Output|
                                       -> Result<(), RenderError> {
                                 Ok(())
                             }));
     }
+
+    #[test]
+    fn content_type_falls_back_to_html_when_unset() {
+        assert_eq!(effective_content_type(None), ContentType::html());
+    }
+
+    #[test]
+    fn content_type_honors_override() {
+        let json_mime: Mime = "application/json".parse().unwrap();
+        assert_eq!(effective_content_type(Some(json_mime.clone())),
+                   ContentType(json_mime));
+    }
+
+    #[test]
+    fn layout_override_wins_over_engine_default() {
+        let engine_layout = Some("default".to_owned());
+        assert_eq!(effective_layout(&None, &engine_layout), engine_layout);
+        assert_eq!(effective_layout(&Some(Some("custom".to_owned())), &engine_layout),
+                   Some("custom".to_owned()));
+        assert_eq!(effective_layout(&Some(None), &engine_layout), None);
+    }
+
+    #[test]
+    fn with_body_injects_into_an_object_value() {
+        let mut data = BTreeMap::new();
+        data.insert("title".to_owned(), "hi".to_owned());
+        let value = ::serde_json::to_value(&data).unwrap();
+
+        let wrapped = with_body(&value, "<p>hi</p>".to_owned());
+        assert_eq!(wrapped["title"], "hi");
+        assert_eq!(wrapped["body"], "<p>hi</p>");
+    }
+
+    #[test]
+    fn with_body_wraps_a_non_object_value() {
+        let wrapped = with_body(&::serde_json::Value::Null, "page".to_owned());
+        assert_eq!(wrapped["body"], "page");
+    }
 }
@@ -0,0 +1,101 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use handlebars::{Handlebars, TemplateError};
+
+#[derive(Debug)]
+pub enum SourceError {
+    IoError(io::Error),
+    TemplateError(Box<TemplateError>),
+}
+
+impl fmt::Display for SourceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SourceError::IoError(ref e) => write!(f, "{}", e),
+            SourceError::TemplateError(ref e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for SourceError {}
+
+impl From<io::Error> for SourceError {
+    fn from(e: io::Error) -> SourceError {
+        SourceError::IoError(e)
+    }
+}
+
+impl From<TemplateError> for SourceError {
+    fn from(e: TemplateError) -> SourceError {
+        SourceError::TemplateError(Box::new(e))
+    }
+}
+
+/// Something that can populate a `Handlebars` registry with templates.
+///
+/// Beyond the mandatory `load`, a source may optionally support cheap
+/// change-detection so `HandlebarsEngine` can skip re-parsing templates
+/// that have not changed since the last reload. The default
+/// implementations assume the source cannot tell, and always report a
+/// change / fall back to a full `load`.
+pub trait Source {
+    /// (Re-)register every template this source knows about.
+    fn load(&self, reg: &mut Handlebars<'static>) -> Result<(), SourceError>;
+
+    /// Cheaply check whether this source has anything to re-register since
+    /// the last `load`/`reload_if_changed` call, without touching the
+    /// registry. Used to decide whether it's worth taking the registry's
+    /// write lock at all.
+    fn has_changed(&self) -> Result<bool, SourceError> {
+        Ok(true)
+    }
+
+    /// Re-register only what changed since the last call, removing
+    /// templates for anything that disappeared, and report whether the
+    /// registry was actually touched.
+    fn reload_if_changed(&self, reg: &mut Handlebars<'static>) -> Result<bool, SourceError> {
+        self.load(reg)?;
+        Ok(true)
+    }
+}
+
+/// Strips `prefix` and `suffix` off a file path (normalizing `\` to `/`
+/// along the way) to derive the name a `Source` registers it under.
+///
+/// Shared by the directory-backed sources (`DirectorySource`, `ScriptSource`,
+/// `EmbeddedSource`) so the naming convention stays identical across them.
+pub fn strip_prefix_suffix(path: &str, prefix: &str, suffix: &str) -> String {
+    let path = path.replace('\\', "/");
+    let prefix = prefix.replace('\\', "/");
+    let path = path.trim_start_matches(prefix.as_str()).trim_start_matches('/');
+    match path.strip_suffix(suffix) {
+        Some(stripped) => stripped.to_owned(),
+        None => path.to_owned(),
+    }
+}
+
+/// Recursively collects every file under `dir` whose path ends with `suffix`.
+///
+/// Shared by the filesystem-backed sources (`DirectorySource`, `ScriptSource`).
+pub fn walk_files(dir: &Path, suffix: &str) -> Result<Vec<PathBuf>, SourceError> {
+    let mut files = Vec::new();
+    walk_files_into(dir, suffix, &mut files)?;
+    Ok(files)
+}
+
+fn walk_files_into(dir: &Path, suffix: &str, files: &mut Vec<PathBuf>) -> Result<(), SourceError> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_dir() {
+            walk_files_into(&path, suffix, files)?;
+        } else if path.to_string_lossy().ends_with(suffix) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use handlebars::Handlebars;
+
+use crate::source::{self, Source, SourceError};
+
+/// A `Source` that registers every `<prefix>/**/*<suffix>` file it finds on
+/// disk as a template, named after its path relative to `prefix` with the
+/// suffix stripped off.
+///
+/// `DirectorySource` remembers the modification time it last saw for each
+/// file, so `reload_if_changed` only re-parses templates whose file
+/// actually changed, and drops templates whose file was deleted.
+pub struct DirectorySource {
+    pub prefix: String,
+    pub suffix: String,
+    seen: Mutex<HashMap<PathBuf, SystemTime>>,
+}
+
+impl DirectorySource {
+    pub fn new(prefix: &str, suffix: &str) -> DirectorySource {
+        DirectorySource {
+            prefix: prefix.to_owned(),
+            suffix: suffix.to_owned(),
+            seen: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn template_name(&self, path: &Path) -> String {
+        source::strip_prefix_suffix(&path.to_string_lossy(), &self.prefix, &self.suffix)
+    }
+
+    fn scan(&self) -> Result<HashMap<PathBuf, SystemTime>, SourceError> {
+        let files = source::walk_files(Path::new(&self.prefix), &self.suffix)?;
+
+        let mut found = HashMap::new();
+        for path in files {
+            let modified = fs::metadata(&path)?.modified()?;
+            found.insert(path, modified);
+        }
+        Ok(found)
+    }
+}
+
+impl Source for DirectorySource {
+    fn load(&self, reg: &mut Handlebars<'static>) -> Result<(), SourceError> {
+        let found = self.scan()?;
+
+        for path in found.keys() {
+            let name = self.template_name(path);
+            reg.register_template_file(&name, path)?;
+        }
+
+        *self.seen.lock().unwrap() = found;
+        Ok(())
+    }
+
+    fn has_changed(&self) -> Result<bool, SourceError> {
+        let found = self.scan()?;
+        let seen = self.seen.lock().unwrap();
+        Ok(found != *seen)
+    }
+
+    fn reload_if_changed(&self, reg: &mut Handlebars<'static>) -> Result<bool, SourceError> {
+        let found = self.scan()?;
+        let mut seen = self.seen.lock().unwrap();
+        let mut changed = false;
+
+        for (path, modified) in found.iter() {
+            let up_to_date = seen.get(path) == Some(modified);
+            if !up_to_date {
+                let name = self.template_name(path);
+                reg.register_template_file(&name, path)?;
+                changed = true;
+            }
+        }
+
+        for path in seen.keys() {
+            if !found.contains_key(path) {
+                reg.unregister_template(&self.template_name(path));
+                changed = true;
+            }
+        }
+
+        *seen = found;
+        Ok(changed)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs::{self, File};
+    use std::io::Write;
+    use std::path::Path;
+    use std::thread;
+    use std::time::Duration;
+
+    use handlebars::Handlebars;
+
+    use crate::source::Source;
+
+    use super::DirectorySource;
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("handlebars-iron-test-{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write(dir: &Path, name: &str, content: &str) {
+        let mut f = File::create(dir.join(name)).unwrap();
+        f.write_all(content.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn template_name_strips_prefix_and_suffix() {
+        let dir = temp_dir("naming");
+        write(&dir, "index.hbs", "hello");
+
+        let source = DirectorySource::new(dir.to_str().unwrap(), ".hbs");
+        let mut reg = Handlebars::new();
+        source.load(&mut reg).unwrap();
+
+        assert!(reg.get_template("index").is_some());
+    }
+
+    #[test]
+    fn reload_if_changed_skips_untouched_files_and_drops_deleted_ones() {
+        let dir = temp_dir("reload");
+        write(&dir, "a.hbs", "a");
+        write(&dir, "b.hbs", "b");
+
+        let source = DirectorySource::new(dir.to_str().unwrap(), ".hbs");
+        let mut reg = Handlebars::new();
+        source.load(&mut reg).unwrap();
+
+        assert!(!source.has_changed().unwrap());
+
+        // Touch "a" with a later mtime so it's the only one re-registered.
+        thread::sleep(Duration::from_millis(20));
+        write(&dir, "a.hbs", "a2");
+        assert!(source.has_changed().unwrap());
+        assert!(source.reload_if_changed(&mut reg).unwrap());
+        assert!(!source.has_changed().unwrap());
+
+        fs::remove_file(dir.join("b.hbs")).unwrap();
+        assert!(source.has_changed().unwrap());
+        assert!(source.reload_if_changed(&mut reg).unwrap());
+        assert!(reg.get_template("b").is_none());
+    }
+}
@@ -0,0 +1,94 @@
+use std::io;
+use std::marker::PhantomData;
+use std::str;
+
+use rust_embed::RustEmbed;
+
+use handlebars::Handlebars;
+
+use crate::source::{self, Source, SourceError};
+
+/// A `Source` backed by files compiled into the binary via `#[derive(RustEmbed)]`.
+///
+/// It behaves like `DirectorySource` but needs nothing on disk at runtime,
+/// which makes it a good fit for single-binary deployments. The template
+/// name is the embedded file's path with `prefix` and `suffix` stripped.
+pub struct EmbeddedSource<T: RustEmbed> {
+    pub prefix: String,
+    pub suffix: String,
+    _marker: PhantomData<T>,
+}
+
+impl<T: RustEmbed> EmbeddedSource<T> {
+    pub fn new(suffix: &str) -> EmbeddedSource<T> {
+        EmbeddedSource::with_prefix("", suffix)
+    }
+
+    pub fn with_prefix(prefix: &str, suffix: &str) -> EmbeddedSource<T> {
+        EmbeddedSource {
+            prefix: prefix.to_owned(),
+            suffix: suffix.to_owned(),
+            _marker: PhantomData,
+        }
+    }
+
+    fn template_name(&self, file: &str) -> String {
+        source::strip_prefix_suffix(file, &self.prefix, &self.suffix)
+    }
+}
+
+impl<T: RustEmbed> Source for EmbeddedSource<T> {
+    fn load(&self, reg: &mut Handlebars<'static>) -> Result<(), SourceError> {
+        for file in T::iter() {
+            let file = file.as_ref();
+            if !file.starts_with(&self.prefix) || !file.ends_with(&self.suffix) {
+                continue;
+            }
+
+            let embedded = T::get(file).expect("file returned by iter() must be gettable");
+            let content = str::from_utf8(embedded.data.as_ref())
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+            let name = self.template_name(file);
+            reg.register_template_string(&name, content)?;
+        }
+        Ok(())
+    }
+
+    /// Assets are compiled into the binary, so there is never anything new
+    /// to pick up at runtime: a full reload can never find a change.
+    fn has_changed(&self) -> Result<bool, SourceError> {
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use handlebars::Handlebars;
+    use rust_embed::RustEmbed;
+
+    use crate::source::Source;
+
+    use super::EmbeddedSource;
+
+    #[derive(RustEmbed)]
+    #[folder = "src/sources"]
+    #[include = "*.rs"]
+    struct Assets;
+
+    #[test]
+    fn has_changed_is_always_false() {
+        let source = EmbeddedSource::<Assets>::new(".rs");
+        assert!(!source.has_changed().unwrap());
+    }
+
+    #[test]
+    fn load_registers_every_matching_embedded_file() {
+        let source = EmbeddedSource::<Assets>::new(".rs");
+        let mut reg = Handlebars::new();
+        source.load(&mut reg).unwrap();
+
+        assert!(reg.get_template("directory").is_some());
+        assert!(reg.get_template("mod").is_some());
+    }
+}
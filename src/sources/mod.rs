@@ -0,0 +1,7 @@
+pub mod directory;
+
+#[cfg(feature = "rust-embed")]
+pub mod embedded;
+
+#[cfg(feature = "script_helper")]
+pub mod script;
@@ -0,0 +1,87 @@
+use std::io;
+use std::path::Path;
+
+use handlebars::Handlebars;
+
+use crate::source::{self, Source, SourceError};
+
+/// A `Source` that registers every `.rhai` script under a directory as a
+/// helper, named after the file with its extension stripped.
+///
+/// This lets users add or tweak formatting/logic helpers by dropping a
+/// script next to their templates, without recompiling the server. Like
+/// `DirectorySource`, it composes with `HandlebarsEngine::add` and
+/// participates in `reload()`/dev mode.
+pub struct ScriptSource {
+    pub prefix: String,
+    pub suffix: String,
+}
+
+impl ScriptSource {
+    pub fn new(prefix: &str) -> ScriptSource {
+        ScriptSource {
+            prefix: prefix.to_owned(),
+            suffix: ".rhai".to_owned(),
+        }
+    }
+
+    fn helper_name(&self, path: &Path) -> String {
+        source::strip_prefix_suffix(&path.to_string_lossy(), &self.prefix, &self.suffix)
+    }
+}
+
+impl Source for ScriptSource {
+    fn load(&self, reg: &mut Handlebars<'static>) -> Result<(), SourceError> {
+        let files = source::walk_files(Path::new(&self.prefix), &self.suffix)?;
+
+        for path in files {
+            let name = self.helper_name(&path);
+            // `handlebars::ScriptError` isn't a public type, so it can't be
+            // named in a `From` impl for `SourceError`; fold it into an
+            // `io::Error` via its `Display` output instead.
+            reg.register_script_helper_file(&name, &path)
+                .map_err(|e| io::Error::other(e.to_string()))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs::{self, File};
+    use std::io::Write;
+    use std::path::Path;
+
+    use handlebars::Handlebars;
+
+    use crate::source::Source;
+
+    use super::ScriptSource;
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("handlebars-iron-test-script-{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write(dir: &Path, name: &str, content: &str) {
+        let mut f = File::create(dir.join(name)).unwrap();
+        f.write_all(content.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn loads_rhai_helper_named_after_its_file() {
+        let dir = temp_dir("basic");
+        write(&dir, "shout.rhai", "params[0].to_upper()");
+
+        let source = ScriptSource::new(dir.to_str().unwrap());
+        let mut reg = Handlebars::new();
+        source.load(&mut reg).unwrap();
+
+        reg.register_template_string("t", "{{shout name}}").unwrap();
+        let mut data = std::collections::BTreeMap::new();
+        data.insert("name".to_owned(), "hi".to_owned());
+        assert_eq!(reg.render("t", &data).unwrap(), "HI");
+    }
+}
@@ -0,0 +1,71 @@
+use std::path::Path;
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::middleware::HandlebarsEngine;
+
+/// Time to wait for more filesystem events before reloading, so a burst of
+/// writes from an editor or a `cargo build` only triggers a single reload.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Things that can watch a directory in the background and keep themselves
+/// up to date as files under it change.
+pub trait Watchable {
+    /// Spawns a background thread that watches `path` and calls `reload()`
+    /// whenever something under it is created, modified, or removed.
+    fn watch(&self, path: &str);
+}
+
+impl Watchable for Arc<HandlebarsEngine> {
+    fn watch(&self, path: &str) {
+        let engine = self.clone();
+        let path = path.to_owned();
+
+        thread::spawn(move || {
+            let (tx, rx) = channel();
+            let mut watcher: RecommendedWatcher = match RecommendedWatcher::new(tx, Config::default()) {
+                Ok(w) => w,
+                Err(e) => {
+                    warn!("failed to start template watcher for {}: {}", path, e);
+                    return;
+                }
+            };
+
+            if let Err(e) = watcher.watch(Path::new(&path), RecursiveMode::Recursive) {
+                warn!("failed to watch {}: {}", path, e);
+                return;
+            }
+
+            let is_change = |event: &notify::Result<Event>| match *event {
+                Ok(ref e) => e.kind.is_create() || e.kind.is_modify() || e.kind.is_remove(),
+                Err(_) => false,
+            };
+
+            loop {
+                // Block for the first create/modify/remove event, then drain
+                // whatever else arrives within the debounce window before
+                // reloading once.
+                match rx.recv() {
+                    Ok(ref event) if is_change(event) => {}
+                    Ok(_) => continue,
+                    Err(_) => break,
+                }
+                loop {
+                    match rx.recv_timeout(DEBOUNCE) {
+                        Ok(_) => continue,
+                        Err(RecvTimeoutError::Timeout) => break,
+                        Err(RecvTimeoutError::Disconnected) => return,
+                    }
+                }
+
+                if let Err(e) = engine.reload() {
+                    warn!("failed to reload templates from {}: {}", path, e);
+                }
+            }
+        });
+    }
+}